@@ -0,0 +1,56 @@
+//! On-disk cache of fetched WorkFlowy nodes, keyed by target id and
+//! content-hashed so stale network reads can be detected and, when the
+//! network is unavailable, the cached copy can be served instead.
+
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+
+use crate::errors::AppError;
+
+pub struct CacheEntry {
+    pub body: Vec<u8>,
+    pub digest: String,
+}
+
+pub(crate) fn digest(body: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(body);
+    format!("{:x}", hasher.finalize())
+}
+
+fn cache_paths(app_data: &Path, target_id: &str) -> (PathBuf, PathBuf) {
+    let key = urlencoding::encode(target_id).into_owned();
+    let dir = app_data.join("cache");
+    (dir.join(format!("{key}.json")), dir.join(format!("{key}.digest")))
+}
+
+/// Write `body` for `target_id` to the cache, returning its digest.
+pub fn write(app_data: &Path, target_id: &str, body: &[u8]) -> Result<String, AppError> {
+    let (body_path, digest_path) = cache_paths(app_data, target_id);
+    std::fs::create_dir_all(body_path.parent().expect("cache path has a parent")).map_err(AppError::AppData)?;
+
+    let hash = digest(body);
+    std::fs::write(&body_path, body).map_err(AppError::AppData)?;
+    std::fs::write(&digest_path, &hash).map_err(AppError::AppData)?;
+    Ok(hash)
+}
+
+/// Read back a previously cached entry for `target_id`, if any.
+pub fn read(app_data: &Path, target_id: &str) -> Option<CacheEntry> {
+    let (body_path, digest_path) = cache_paths(app_data, target_id);
+    let body = std::fs::read(&body_path).ok()?;
+    let digest = std::fs::read_to_string(&digest_path).ok()?;
+    Some(CacheEntry { body, digest })
+}
+
+/// Drop the cached entry for `target_id`, if any.
+pub fn invalidate(app_data: &Path, target_id: &str) -> Result<(), AppError> {
+    let (body_path, digest_path) = cache_paths(app_data, target_id);
+    for path in [&body_path, &digest_path] {
+        if path.exists() {
+            std::fs::remove_file(path).map_err(AppError::AppData)?;
+        }
+    }
+    Ok(())
+}
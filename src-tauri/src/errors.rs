@@ -0,0 +1,57 @@
+//! Crate-level error type. Kept as a single enum (rather than `String`s built
+//! with `format!`) so it can cross the Tauri IPC boundary as structured JSON
+//! and so async protocol handlers can turn a failure into a real HTTP
+//! response instead of panicking.
+
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum AppError {
+    #[error("file operation failed: {0}")]
+    AppData(std::io::Error),
+
+    #[error(transparent)]
+    Tauri(#[from] tauri::Error),
+
+    #[error("request to WorkFlowy failed: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("WorkFlowy API error ({status}): {body}")]
+    Upstream { status: u16, body: String },
+
+    #[error("response body exceeded the {limit}-byte limit")]
+    PayloadTooLarge { limit: usize },
+
+    #[error(transparent)]
+    Infallible(#[from] std::convert::Infallible),
+}
+
+impl serde::Serialize for AppError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl AppError {
+    /// Best-effort HTTP status to report a given error as, used by the
+    /// `mcp://` protocol handler when turning an `AppError` into a response.
+    pub fn status_code(&self) -> u16 {
+        match self {
+            AppError::Upstream { status, .. } => *status,
+            AppError::Http(_) => 502,
+            AppError::PayloadTooLarge { .. } => 413,
+            _ => 500,
+        }
+    }
+}
+
+impl axum::response::IntoResponse for AppError {
+    fn into_response(self) -> axum::response::Response {
+        let status = axum::http::StatusCode::from_u16(self.status_code())
+            .unwrap_or(axum::http::StatusCode::INTERNAL_SERVER_ERROR);
+        (status, self.to_string()).into_response()
+    }
+}
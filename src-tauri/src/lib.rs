@@ -1,63 +1,96 @@
 use tauri::Manager;
-use std::path::PathBuf;
 
-/// Copy the MCP server from the app bundle to the user's data directory
-fn copy_mcp_server(app: &tauri::App) -> Result<PathBuf, String> {
-    let app_data = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+mod cache;
+mod errors;
+mod mcp_bridge;
 
-    let resource_path = app
-        .path()
-        .resource_dir()
-        .map_err(|e| format!("Failed to get resource dir: {}", e))?;
+use errors::AppError;
+
+/// Outcome of a `copy_mcp_server` run, so the caller can log (and eventually
+/// the frontend can surface) whether an update actually happened.
+#[derive(Debug)]
+enum ServerSyncStatus {
+    Copied,
+    UpToDate,
+    SourceMissing,
+}
+
+/// Copy the MCP server from the app bundle to the user's data directory,
+/// skipping the copy when the bundled and installed copies already match.
+fn copy_mcp_server(app: &tauri::App) -> Result<ServerSyncStatus, AppError> {
+    let app_data = app.path().app_data_dir()?;
+    let resource_path = app.path().resource_dir()?;
 
     // The server is bundled at _up_/dist-mcp/server.cjs (due to ../dist-mcp in tauri.conf.json)
     let source = resource_path.join("_up_").join("dist-mcp").join("server.cjs");
     let dest = app_data.join("server.cjs");
+    let version_file = app_data.join("server.version");
 
-    // Always copy to ensure we have the latest version
-    if source.exists() {
-        std::fs::create_dir_all(&app_data)
-            .map_err(|e| format!("Failed to create data dir: {}", e))?;
-        std::fs::copy(&source, &dest)
-            .map_err(|e| format!("Failed to copy server: {}", e))?;
+    if !source.exists() {
+        return Ok(ServerSyncStatus::SourceMissing);
     }
 
-    Ok(dest)
+    let source_hash = cache::digest(&std::fs::read(&source).map_err(AppError::AppData)?);
+
+    // Hash dest's actual bytes rather than trusting server.version alone, so
+    // a crashed prior copy or an out-of-band edit to dest is still detected.
+    let dest_hash = if dest.exists() {
+        Some(cache::digest(&std::fs::read(&dest).map_err(AppError::AppData)?))
+    } else {
+        None
+    };
+
+    if dest_hash.as_deref() == Some(source_hash.as_str()) {
+        // Keep the version file in sync even when we skip the copy.
+        std::fs::write(&version_file, &source_hash).map_err(AppError::AppData)?;
+        return Ok(ServerSyncStatus::UpToDate);
+    }
+
+    std::fs::create_dir_all(&app_data).map_err(AppError::AppData)?;
+    std::fs::copy(&source, &dest).map_err(AppError::AppData)?;
+    std::fs::write(&version_file, &source_hash).map_err(AppError::AppData)?;
+
+    Ok(ServerSyncStatus::Copied)
 }
 
 #[tauri::command]
-async fn validate_api_key(api_key: String) -> Result<bool, String> {
+async fn validate_api_key(api_key: String) -> Result<bool, AppError> {
     let client = reqwest::Client::new();
     let response = client
         .get("https://workflowy.com/api/v1/targets")
         .header("Authorization", format!("Bearer {}", api_key.trim()))
         .header("Content-Type", "application/json")
         .send()
-        .await
-        .map_err(|e| format!("Request failed: {}", e))?;
+        .await?;
 
     if response.status().is_success() {
         Ok(true)
     } else {
-        let status = response.status();
-        let text = response.text().await.unwrap_or_default();
-        Err(format!("API error ({}): {}", status, text))
+        let status = response.status().as_u16();
+        let body = response.text().await.unwrap_or_default();
+        Err(AppError::Upstream { status, body })
     }
 }
 
 #[tauri::command]
-fn get_server_path(app_handle: tauri::AppHandle) -> Result<String, String> {
-    let app_data = app_handle
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+fn get_server_path(app_handle: tauri::AppHandle) -> Result<String, AppError> {
+    let app_data = app_handle.path().app_data_dir()?;
     let server_path = app_data.join("server.cjs");
     Ok(server_path.to_string_lossy().to_string())
 }
 
+#[tauri::command]
+fn get_cached_tree(app_handle: tauri::AppHandle, target_id: String) -> Result<Option<String>, AppError> {
+    let app_data = app_handle.path().app_data_dir()?;
+    Ok(cache::read(&app_data, &target_id).map(|entry| String::from_utf8_lossy(&entry.body).into_owned()))
+}
+
+#[tauri::command]
+fn invalidate_cache(app_handle: tauri::AppHandle, target_id: String) -> Result<(), AppError> {
+    let app_data = app_handle.path().app_data_dir()?;
+    cache::invalidate(&app_data, &target_id)
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
@@ -65,12 +98,31 @@ pub fn run() {
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_http::init())
-        .invoke_handler(tauri::generate_handler![validate_api_key, get_server_path])
+        .register_asynchronous_uri_scheme_protocol("mcp", move |ctx, request, responder| {
+            let router = ctx.app_handle().state::<mcp_bridge::McpRouter>().inner().clone();
+            tauri::async_runtime::spawn(async move {
+                let response = mcp_bridge::handle_request(router, request, mcp_bridge::DEFAULT_MAX_BODY_BYTES).await;
+                responder.respond(response);
+            });
+        })
+        .invoke_handler(tauri::generate_handler![
+            validate_api_key,
+            get_server_path,
+            get_cached_tree,
+            invalidate_cache
+        ])
         .setup(|app| {
-            // Copy MCP server from bundle to data directory
+            // Built here (rather than before the app exists) so the router can
+            // be seeded with the real app data directory for its on-disk cache.
+            let app_data = app.path().app_data_dir()?;
+            app.manage(mcp_bridge::build_router(app_data, mcp_bridge::DEFAULT_MAX_BODY_BYTES));
+
+            // Copy MCP server from bundle to data directory, if it changed
             match copy_mcp_server(app) {
-                Ok(path) => println!("MCP server copied to: {:?}", path),
-                Err(e) => eprintln!("Warning: Failed to copy MCP server: {}", e),
+                Ok(ServerSyncStatus::Copied) => println!("MCP server updated in data directory"),
+                Ok(ServerSyncStatus::UpToDate) => println!("MCP server already up to date"),
+                Ok(ServerSyncStatus::SourceMissing) => eprintln!("Warning: bundled MCP server not found"),
+                Err(e) => eprintln!("Warning: Failed to sync MCP server: {}", e),
             }
             Ok(())
         })
@@ -0,0 +1,243 @@
+//! In-process MCP bridge: an axum `Router` that proxies WorkFlowy tool calls
+//! to `https://workflowy.com/api/v1`, served to the webview over the `mcp://`
+//! custom URI scheme instead of shelling out to the bundled `server.cjs`.
+//!
+//! Response bodies are read in bounded chunks (`collect_upstream`/
+//! `collect_body`) so an oversized tree is rejected before it's fully
+//! buffered, rather than via an unbounded `to_bytes(body, usize::MAX)`/
+//! `.bytes()` read. This is a memory guard on the read side only: Tauri's
+//! `UriSchemeResponder::respond` takes a complete `Response<Vec<u8>>`, so
+//! there is no incremental body delivery to the webview here.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    extract::{Path, Query, Request, State},
+    routing::{get, post},
+    Json, Router,
+};
+use futures_util::StreamExt;
+use tokio::sync::Mutex;
+
+use crate::cache;
+use crate::errors::AppError;
+
+const WORKFLOWY_API_BASE: &str = "https://workflowy.com/api/v1";
+
+/// Upper bound on a proxied body, so a pathological WorkFlowy export can't
+/// exhaust memory before we notice it's too big to hand to the webview.
+pub const DEFAULT_MAX_BODY_BYTES: usize = 25 * 1024 * 1024;
+
+/// Shared handle to the bridge router, managed as Tauri app state.
+pub type McpRouter = Arc<Mutex<Router>>;
+
+#[derive(Clone)]
+struct BridgeState {
+    client: reqwest::Client,
+    app_data_dir: PathBuf,
+    max_body_bytes: usize,
+}
+
+pub fn build_router(app_data_dir: PathBuf, max_body_bytes: usize) -> McpRouter {
+    let state = BridgeState {
+        client: reqwest::Client::new(),
+        app_data_dir,
+        max_body_bytes,
+    };
+
+    let router = Router::new()
+        .route("/nodes/:id", get(list_node).patch(update_node))
+        .route("/nodes", post(create_node))
+        .route("/search", get(search_nodes))
+        .with_state(state);
+
+    Arc::new(Mutex::new(router))
+}
+
+fn forward_auth(headers: &axum::http::HeaderMap, req: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+    match headers.get(axum::http::header::AUTHORIZATION) {
+        Some(value) => req.header(axum::http::header::AUTHORIZATION, value),
+        None => req,
+    }
+}
+
+/// Read a `reqwest::Response` body incrementally, enforcing `limit` as the
+/// chunks arrive so a pathological body is rejected before it's fully
+/// buffered, rather than only after an unbounded `.bytes()`/`.json()` read.
+/// The full (bounded) body is still returned as one `Vec<u8>` — this is a
+/// memory guard on the read, not an end-to-end stream to the webview.
+async fn collect_upstream(response: reqwest::Response, limit: usize) -> Result<Vec<u8>, AppError> {
+    let mut stream = response.bytes_stream();
+    let mut collected = Vec::new();
+    while let Some(chunk) = stream.next().await {
+        collected.extend_from_slice(&chunk?);
+        if collected.len() > limit {
+            return Err(AppError::PayloadTooLarge { limit });
+        }
+    }
+    Ok(collected)
+}
+
+/// Same as `collect_upstream`, but for the axum body we get back from driving
+/// a Tauri request through the in-process router. Still a bounded, buffered
+/// read: `tauri::http::Response` carries a plain `Vec<u8>` body, so there is
+/// no incremental hand-off to the webview on the other side of this call.
+async fn collect_body(body: Body, limit: usize) -> Result<Vec<u8>, AppError> {
+    let mut stream = body.into_data_stream();
+    let mut collected = Vec::new();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| AppError::Upstream {
+            status: 502,
+            body: e.to_string(),
+        })?;
+        collected.extend_from_slice(&chunk);
+        if collected.len() > limit {
+            return Err(AppError::PayloadTooLarge { limit });
+        }
+    }
+    Ok(collected)
+}
+
+async fn parse_response(response: reqwest::Response, limit: usize) -> Result<serde_json::Value, AppError> {
+    if !response.status().is_success() {
+        let status = response.status().as_u16();
+        let body = response.text().await.unwrap_or_default();
+        return Err(AppError::Upstream { status, body });
+    }
+    let bytes = collect_upstream(response, limit).await?;
+    parse_bytes(&bytes)
+}
+
+fn parse_bytes(bytes: &[u8]) -> Result<serde_json::Value, AppError> {
+    serde_json::from_slice(bytes).map_err(|e| AppError::Upstream {
+        status: 502,
+        body: e.to_string(),
+    })
+}
+
+/// Fetch a node, sending the cached digest as a conditional validator so an
+/// unchanged tree is served from the local cache instead of re-downloaded.
+/// Falls back to the cached copy outright if the upstream request cannot be
+/// sent (e.g. offline); an upstream error status is returned as an error.
+async fn list_node(
+    State(state): State<BridgeState>,
+    Path(id): Path<String>,
+    headers: axum::http::HeaderMap,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let cached = cache::read(&state.app_data_dir, &id);
+
+    let mut request = forward_auth(&headers, state.client.get(format!("{WORKFLOWY_API_BASE}/nodes/{id}")));
+    if let Some(entry) = &cached {
+        request = request.header(axum::http::header::IF_NONE_MATCH, format!("\"{}\"", entry.digest));
+    }
+
+    match request.send().await {
+        Ok(response) if response.status() == reqwest::StatusCode::NOT_MODIFIED => match cached {
+            Some(entry) => Ok(Json(parse_bytes(&entry.body)?)),
+            None => Err(AppError::Upstream {
+                status: 304,
+                body: "upstream reported unchanged but no cache entry exists".to_string(),
+            }),
+        },
+        Ok(response) if response.status().is_success() => {
+            let bytes = collect_upstream(response, state.max_body_bytes).await?;
+            if cached.as_ref().map(|entry| entry.digest.as_str()) != Some(cache::digest(&bytes).as_str()) {
+                let _ = cache::write(&state.app_data_dir, &id, &bytes);
+            }
+            Ok(Json(parse_bytes(&bytes)?))
+        }
+        Ok(response) => {
+            let status = response.status().as_u16();
+            let body = response.text().await.unwrap_or_default();
+            Err(AppError::Upstream { status, body })
+        }
+        Err(err) => match cached {
+            Some(entry) => Ok(Json(parse_bytes(&entry.body)?)),
+            None => Err(AppError::from(err)),
+        },
+    }
+}
+
+async fn search_nodes(
+    State(state): State<BridgeState>,
+    Query(params): Query<std::collections::HashMap<String, String>>,
+    headers: axum::http::HeaderMap,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let query = params.get("q").cloned().unwrap_or_default();
+    let request = forward_auth(
+        &headers,
+        state
+            .client
+            .get(format!("{WORKFLOWY_API_BASE}/search"))
+            .query(&[("q", query)]),
+    );
+    Ok(Json(parse_response(request.send().await?, state.max_body_bytes).await?))
+}
+
+async fn create_node(
+    State(state): State<BridgeState>,
+    headers: axum::http::HeaderMap,
+    Json(payload): Json<serde_json::Value>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let request = forward_auth(&headers, state.client.post(format!("{WORKFLOWY_API_BASE}/nodes")));
+    Ok(Json(
+        parse_response(request.json(&payload).send().await?, state.max_body_bytes).await?,
+    ))
+}
+
+async fn update_node(
+    State(state): State<BridgeState>,
+    Path(id): Path<String>,
+    headers: axum::http::HeaderMap,
+    Json(payload): Json<serde_json::Value>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    let request = forward_auth(&headers, state.client.patch(format!("{WORKFLOWY_API_BASE}/nodes/{id}")));
+    Ok(Json(
+        parse_response(request.json(&payload).send().await?, state.max_body_bytes).await?,
+    ))
+}
+
+/// Build a `tauri::http::Response` reporting `err`, rather than letting a
+/// failure deep in the bridge tear down the protocol handler.
+fn error_response(err: AppError) -> tauri::http::Response<Vec<u8>> {
+    tauri::http::Response::builder()
+        .status(err.status_code())
+        .body(err.to_string().into_bytes())
+        .unwrap_or_else(|_| tauri::http::Response::new(Vec::new()))
+}
+
+/// Drive a raw Tauri protocol request through the axum router. The response
+/// body is read incrementally and capped at `max_body_bytes` — rather than an
+/// unbounded `to_bytes(body, usize::MAX)` read — but the
+/// `responder` API still only accepts a complete `Vec<u8>` body, so this is
+/// a bounded read guard, not incremental delivery to the webview.
+pub async fn handle_request(
+    router: McpRouter,
+    request: tauri::http::Request<Vec<u8>>,
+    max_body_bytes: usize,
+) -> tauri::http::Response<Vec<u8>> {
+    let (parts, body) = request.into_parts();
+    let axum_request = Request::from_parts(parts, Body::from(body));
+
+    let response = {
+        use tower::{Service, ServiceExt};
+        let mut router = router.lock().await;
+        match router.as_service().ready().await {
+            Ok(service) => match service.call(axum_request).await {
+                Ok(response) => response,
+                Err(infallible) => match infallible {},
+            },
+            Err(infallible) => match infallible {},
+        }
+    };
+
+    // Content-Type and Transfer-Encoding live in `parts.headers` and travel
+    // along untouched; only the body is read incrementally below.
+    let (parts, body) = response.into_parts();
+    match collect_body(body, max_body_bytes).await {
+        Ok(bytes) => tauri::http::Response::from_parts(parts, bytes),
+        Err(err) => error_response(err),
+    }
+}